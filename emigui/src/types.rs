@@ -1,14 +1,54 @@
 use crate::{
     color::Color,
     fonts::TextStyle,
+    layout::Id,
     math::{Rect, Vec2},
     mesher::Mesh,
 };
 
 // ----------------------------------------------------------------------------
 
+/// A key on the keyboard, independent of the user's keyboard layout.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum Key {
+    Alt,
+    Backspace,
+    Delete,
+    Down,
+    End,
+    Enter,
+    Escape,
+    Home,
+    Insert,
+    Left,
+    PageDown,
+    PageUp,
+    Right,
+    Space,
+    Tab,
+    Up,
+}
+
+/// State of the modifier keys at the time of an [`Event`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Modifiers {
+    pub alt: bool,
+    pub ctrl: bool,
+    pub shift: bool,
+}
+
+/// A keyboard or text input event, in the order it was received.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Event {
+    KeyDown { key: Key, modifiers: Modifiers },
+    KeyUp { key: Key, modifiers: Modifiers },
+    /// Typed characters, including spaces. Keys that type no character
+    /// (e.g. `Backspace` or the arrow keys) arrive only as `KeyDown`/`KeyUp`.
+    Text(String),
+}
+
 /// What the integration gives to the gui.
-#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[derive(Clone, Debug, Default, Deserialize)]
 pub struct RawInput {
     /// Is the button currently down?
     pub mouse_down: bool,
@@ -21,10 +61,16 @@ pub struct RawInput {
 
     /// Also known as device pixel ratio, > 1 for HDPI screens.
     pub pixels_per_point: f32,
+
+    /// Keyboard and text events that happened since the last frame.
+    pub events: Vec<Event>,
+
+    /// Time in seconds. Used for animations and for long-press detection.
+    pub time: f64,
 }
 
 /// What the gui maintains
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct GuiInput {
     /// Is the button currently down?
     pub mouse_down: bool,
@@ -43,6 +89,12 @@ pub struct GuiInput {
 
     /// Also known as device pixel ratio, > 1 for HDPI screens.
     pub pixels_per_point: f32,
+
+    /// Keyboard and text events that happened since the last frame.
+    pub events: Vec<Event>,
+
+    /// Time in seconds. Used for animations and for long-press detection.
+    pub time: f64,
 }
 
 impl GuiInput {
@@ -54,12 +106,18 @@ impl GuiInput {
             mouse_pos: new.mouse_pos,
             screen_size: new.screen_size,
             pixels_per_point: new.pixels_per_point,
+            events: new.events.clone(),
+            time: new.time,
         }
     }
 }
 
 // ----------------------------------------------------------------------------
 
+/// Per-widget interaction state, gated by `Region::resolve_interact` against
+/// the topmost [`Hitbox`] under the mouse so that, once two overlapping
+/// widgets have been through a frame of resolution, only the frontmost one
+/// ever reports `hovered`/`clicked`/`released`/`active` again.
 #[derive(Clone, Copy, Debug, Default, Serialize)]
 pub struct InteractInfo {
     /// The mouse is hovering above this
@@ -68,15 +126,87 @@ pub struct InteractInfo {
     /// The mouse went got pressed on this thing this frame
     pub clicked: bool,
 
+    /// The mouse was released over this thing this frame, having been
+    /// pressed down on it
+    pub released: bool,
+
     /// The mouse is interacting with this thing (e.g. dragging it)
     pub active: bool,
 
+    /// `RawInput::time` at which the mouse started being held down on this
+    /// widget, if it currently is (or just stopped being).
+    pub down_since: Option<f64>,
+
     /// The region of the screen we are talking about
     pub rect: Rect,
 }
 
 // ----------------------------------------------------------------------------
 
+/// The color palette widgets fall back to when they aren't given an
+/// explicit [`Colorable`](crate::widgets::Colorable) override.
+///
+/// Stored on `Region::options()` so the whole gui can be restyled by
+/// swapping one `Theme` instead of editing colors baked into widget code.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Theme {
+    pub background_color: Color,
+    pub text_color: Color,
+    /// Used for things the user can interact with, e.g. a checked checkbox.
+    pub accent_color: Color,
+    pub hovered_color: Color,
+    pub separator_color: Color,
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Theme {
+            background_color: Color::from_rgb(27, 27, 27),
+            text_color: Color::WHITE,
+            accent_color: Color::from_rgb(90, 170, 255),
+            hovered_color: Color::from_rgb(70, 70, 70),
+            separator_color: Color::WHITE,
+        }
+    }
+
+    pub fn light() -> Self {
+        Theme {
+            background_color: Color::from_rgb(240, 240, 240),
+            text_color: Color::BLACK,
+            accent_color: Color::from_rgb(0, 92, 185),
+            hovered_color: Color::from_rgb(210, 210, 210),
+            separator_color: Color::BLACK,
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::dark()
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// A candidate interactive area registered by `Region::resolve_interact`
+/// during a frame.
+///
+/// The topmost hitbox (highest `z_layer`, ties broken by later insertion)
+/// whose `rect` contains `mouse_pos` is resolved once the set of hitboxes
+/// registered during a frame is known to be complete — which in practice
+/// means at the start of the *next* frame. Each widget's [`InteractInfo`] is
+/// then gated by comparing its own `Id` against that resolved topmost one, so
+/// overlapping widgets never both report `hovered`/`clicked`; from the second
+/// frame they overlap on, the one reporting it is the frontmost.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Hitbox {
+    pub id: Id,
+    pub rect: Rect,
+    pub z_layer: u32,
+}
+
+// ----------------------------------------------------------------------------
+
 #[derive(Clone, Debug, Serialize)]
 pub struct Outline {
     pub width: f32,
@@ -1,8 +1,10 @@
+use std::any::Any;
+
 use crate::{
     fonts::TextStyle,
     layout::{make_id, Direction, GuiResponse, Id, Region},
-    math::{remap_clamp, vec2, Vec2},
-    types::{Color, GuiCmd, PaintCmd},
+    math::{remap_clamp, vec2, Rect, Vec2},
+    types::{Color, Event, GuiCmd, Hitbox, InteractInfo, Key, PaintCmd},
 };
 
 // ----------------------------------------------------------------------------
@@ -12,11 +14,83 @@ pub trait Widget {
     fn add_to(self, region: &mut Region) -> GuiResponse;
 }
 
+/// Lets a widget's themed color be overridden for that one instance,
+/// instead of always falling back to `Region::options().theme`.
+pub trait Colorable {
+    fn color(self, color: Color) -> Self;
+}
+
+// ----------------------------------------------------------------------------
+
+impl Region {
+    /// Gate a widget's raw [`InteractInfo`] so that when two widgets overlap
+    /// only the topmost one reports `hovered`/`clicked`/`released`/`active`
+    /// this frame, instead of both of them.
+    ///
+    /// Every call registers `id`'s [`Hitbox`] for the frame currently being
+    /// built. The topmost hitbox under the mouse (highest `z_layer`, ties
+    /// going to whichever was registered last) is resolved once that set is
+    /// known to be complete, which is only once the *next* frame starts
+    /// (detected here via `Memory::new_frame`, which the begin-frame hook
+    /// sets before any widget is added). Only the single id resolved that way
+    /// keeps its interaction, so two widgets never both report it; on the
+    /// first frame a pair overlaps, the one that was topmost on the previous
+    /// frame (possibly the one behind) is the one that reports it, and from
+    /// the next frame on it is the topmost.
+    ///
+    /// `id` is `None` for widgets that were never given an explicit/combined
+    /// id (and so can't be hit-tested at all); those pass their `interact`
+    /// through unchanged, same as before this existed.
+    fn resolve_interact(
+        &mut self,
+        id: Option<Id>,
+        rect: Rect,
+        mut interact: InteractInfo,
+    ) -> InteractInfo {
+        let id = match id {
+            Some(id) => id,
+            None => return interact,
+        };
+
+        let mouse_pos = self.input().mouse_pos;
+
+        if self.memory().new_frame {
+            // The hitboxes collected last frame are complete now that a new
+            // frame has started (`Memory::new_frame` is set by the
+            // begin-frame hook before any widget is added), so resolve the
+            // topmost one before collecting this frame's.
+            let topmost = self
+                .memory()
+                .hitboxes
+                .iter()
+                .filter(|hb| mouse_pos.map_or(false, |p| hb.rect.contains(p)))
+                .max_by_key(|hb| hb.z_layer)
+                .map(|hb| hb.id);
+            self.memory().topmost_hitbox = topmost;
+            self.memory().hitboxes.clear();
+            self.memory().new_frame = false;
+        }
+
+        let z_layer = self.memory().next_z_layer;
+        self.memory().next_z_layer += 1;
+        self.memory().hitboxes.push(Hitbox { id, rect, z_layer });
+
+        if self.memory().topmost_hitbox != Some(id) {
+            interact.hovered = false;
+            interact.clicked = false;
+            interact.released = false;
+            interact.active = false;
+        }
+        interact
+    }
+}
+
 // ----------------------------------------------------------------------------
 
 pub struct Label {
     text: String,
     text_style: TextStyle,
+    color: Option<Color>,
 }
 
 impl Label {
@@ -24,6 +98,7 @@ impl Label {
         Label {
             text: text.into(),
             text_style: TextStyle::Body,
+            color: None,
         }
     }
 
@@ -33,6 +108,13 @@ impl Label {
     }
 }
 
+impl Colorable for Label {
+    fn color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+}
+
 pub fn label<S: Into<String>>(text: S) -> Label {
     Label::new(text)
 }
@@ -41,7 +123,8 @@ impl Widget for Label {
     fn add_to(self, region: &mut Region) -> GuiResponse {
         let font = &region.fonts()[self.text_style];
         let (text, text_size) = font.layout_multiline(&self.text, region.width());
-        region.add_text(region.cursor(), self.text_style, text);
+        let color = self.color.unwrap_or(region.options().theme.text_color);
+        region.add_text_colored(region.cursor(), self.text_style, text, color);
         let (_, interact) = region.reserve_space(text_size, None);
         region.response(interact)
     }
@@ -51,11 +134,33 @@ impl Widget for Label {
 
 pub struct Button {
     text: String,
+    touch_expand: Vec2,
+    color: Option<Color>,
 }
 
 impl Button {
     pub fn new<S: Into<String>>(text: S) -> Self {
-        Button { text: text.into() }
+        Button {
+            text: text.into(),
+            touch_expand: Vec2::default(),
+            color: None,
+        }
+    }
+
+    /// Grow the interactive (hit-test) area by this amount on each side,
+    /// without changing the painted button rect. Useful so small or
+    /// large-fingered touch targets still register a press slightly
+    /// outside the visible button.
+    pub fn touch_expand(mut self, expand: Vec2) -> Self {
+        self.touch_expand = expand;
+        self
+    }
+}
+
+impl Colorable for Button {
+    fn color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
     }
 }
 
@@ -65,21 +170,57 @@ impl Widget for Button {
         let text_style = TextStyle::Button;
         let font = &region.fonts()[text_style];
         let (text, text_size) = font.layout_multiline(&self.text, region.width());
-        let text_cursor = region.cursor() + region.options().button_padding;
-        let (rect, interact) =
-            region.reserve_space(text_size + 2.0 * region.options().button_padding, Some(id));
-        region.add_graphic(GuiCmd::Button { interact, rect });
-        region.add_text(text_cursor, text_style, text);
+        let button_size = text_size + 2.0 * region.options().button_padding;
+        let text_cursor =
+            region.cursor() + self.touch_expand + region.options().button_padding;
+        let (hit_rect, interact) = region.reserve_space(
+            button_size + 2.0 * self.touch_expand,
+            Some(id),
+        );
+        let rect = Rect::from_min_size(hit_rect.min() + self.touch_expand, button_size);
+        let interact = region.resolve_interact(Some(id), hit_rect, interact);
+        let color = self.color.unwrap_or(region.options().theme.accent_color);
+        region.add_graphic(GuiCmd::Button {
+            color,
+            interact,
+            rect,
+        });
+        region.add_text_colored(text_cursor, text_style, text, region.options().theme.text_color);
         region.response(interact)
     }
 }
 
 // ----------------------------------------------------------------------------
 
+impl GuiResponse {
+    /// True the frame the mouse is pressed down on this widget.
+    pub fn pressed(&self) -> bool {
+        self.interact.clicked
+    }
+
+    /// True the frame the mouse is released after having been pressed down
+    /// on this widget.
+    pub fn released(&self) -> bool {
+        self.interact.released
+    }
+
+    /// True once the mouse has been held down on this widget for at least
+    /// `threshold` seconds.
+    pub fn long_pressed(&self, threshold: f64) -> bool {
+        match self.interact.down_since {
+            Some(down_since) => self.now - down_since >= threshold,
+            None => false,
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------
+
 #[derive(Debug)]
 pub struct Checkbox<'a> {
     checked: &'a mut bool,
     text: String,
+    color: Option<Color>,
 }
 
 impl<'a> Checkbox<'a> {
@@ -87,10 +228,18 @@ impl<'a> Checkbox<'a> {
         Checkbox {
             checked,
             text: text.into(),
+            color: None,
         }
     }
 }
 
+impl<'a> Colorable for Checkbox<'a> {
+    fn color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+}
+
 impl<'a> Widget for Checkbox<'a> {
     fn add_to(self, region: &mut Region) -> GuiResponse {
         let id = region.make_child_id(&self.text);
@@ -107,15 +256,18 @@ impl<'a> Widget for Checkbox<'a> {
                 + region.options().button_padding,
             Some(id),
         );
+        let interact = region.resolve_interact(Some(id), rect, interact);
         if interact.clicked {
             *self.checked = !*self.checked;
         }
+        let color = self.color.unwrap_or(region.options().theme.accent_color);
         region.add_graphic(GuiCmd::Checkbox {
             checked: *self.checked,
+            color,
             interact,
             rect,
         });
-        region.add_text(text_cursor, text_style, text);
+        region.add_text_colored(text_cursor, text_style, text, region.options().theme.text_color);
         region.response(interact)
     }
 }
@@ -126,6 +278,7 @@ impl<'a> Widget for Checkbox<'a> {
 pub struct RadioButton {
     checked: bool,
     text: String,
+    color: Option<Color>,
 }
 
 impl RadioButton {
@@ -133,10 +286,18 @@ impl RadioButton {
         RadioButton {
             checked,
             text: text.into(),
+            color: None,
         }
     }
 }
 
+impl Colorable for RadioButton {
+    fn color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+}
+
 pub fn radio<S: Into<String>>(checked: bool, text: S) -> RadioButton {
     RadioButton::new(checked, text)
 }
@@ -157,12 +318,15 @@ impl Widget for RadioButton {
                 + region.options().button_padding,
             Some(id),
         );
+        let interact = region.resolve_interact(Some(id), rect, interact);
+        let color = self.color.unwrap_or(region.options().theme.accent_color);
         region.add_graphic(GuiCmd::RadioButton {
             checked: self.checked,
+            color,
             interact,
             rect,
         });
-        region.add_text(text_cursor, text_style, text);
+        region.add_text_colored(text_cursor, text_style, text, region.options().theme.text_color);
         region.response(interact)
     }
 }
@@ -177,6 +341,8 @@ pub struct Slider<'a> {
     id: Option<Id>,
     text: Option<String>,
     text_on_top: Option<bool>,
+    logarithmic: bool,
+    integer: bool,
 }
 
 impl<'a> Slider<'a> {
@@ -188,6 +354,8 @@ impl<'a> Slider<'a> {
             id: None,
             text: None,
             text_on_top: None,
+            logarithmic: false,
+            integer: false,
         }
     }
 
@@ -200,6 +368,21 @@ impl<'a> Slider<'a> {
         self.text = Some(text.into());
         self
     }
+
+    /// Map the slider position to the value logarithmically rather than
+    /// linearly. Useful for ranges spanning many orders of magnitude
+    /// (e.g. 1 Hz to 20 kHz).
+    pub fn logarithmic(mut self, logarithmic: bool) -> Self {
+        self.logarithmic = logarithmic;
+        self
+    }
+
+    /// Round the value (and the displayed handle position) to the nearest
+    /// integer.
+    pub fn integer(mut self, integer: bool) -> Self {
+        self.integer = integer;
+        self
+    }
 }
 
 impl<'a> Widget for Slider<'a> {
@@ -230,6 +413,8 @@ impl<'a> Widget for Slider<'a> {
             let value = self.value;
             let min = self.min;
             let max = self.max;
+            let logarithmic = self.logarithmic;
+            let integer = self.integer;
             debug_assert!(min <= max);
             let id = region.combined_id(self.id);
             let (slider_rect, interact) = region.reserve_space(
@@ -239,19 +424,36 @@ impl<'a> Widget for Slider<'a> {
                 },
                 id,
             );
+            let interact = region.resolve_interact(id, slider_rect, interact);
 
             if interact.active {
-                *value = remap_clamp(
-                    region.input().mouse_pos.x,
-                    slider_rect.min().x,
-                    slider_rect.max().x,
-                    min,
-                    max,
-                );
+                let mut new_value = if logarithmic {
+                    let t = remap_clamp(
+                        region.input().mouse_pos.x,
+                        slider_rect.min().x,
+                        slider_rect.max().x,
+                        0.0,
+                        1.0,
+                    );
+                    value_from_normalized_log(t, min, max)
+                } else {
+                    remap_clamp(
+                        region.input().mouse_pos.x,
+                        slider_rect.min().x,
+                        slider_rect.max().x,
+                        min,
+                        max,
+                    )
+                };
+                if integer {
+                    new_value = new_value.round();
+                }
+                *value = new_value;
             }
 
             region.add_graphic(GuiCmd::Slider {
                 interact,
+                logarithmic,
                 max,
                 min,
                 rect: slider_rect,
@@ -263,11 +465,441 @@ impl<'a> Widget for Slider<'a> {
     }
 }
 
+/// Forward map for [`Slider::logarithmic`]: normalized position `t ∈ [0,1]`
+/// to a value in `[min, max]`.
+///
+/// When `min`/`max` are both positive (or both negative) this is a plain
+/// logarithmic curve. When the range straddles zero it falls back to a
+/// symmetric-log mapping: linear within a small region around zero and
+/// logarithmic further out on each side.
+fn value_from_normalized_log(t: f32, min: f32, max: f32) -> f32 {
+    if min >= 0.0 {
+        positive_value_from_normalized_log(t, min.max(f32::MIN_POSITIVE), max)
+    } else if max <= 0.0 {
+        -positive_value_from_normalized_log(1.0 - t, (-max).max(f32::MIN_POSITIVE), -min)
+    } else {
+        symmetric_value_from_normalized_log(t, min, max)
+    }
+}
+
+/// Inverse of [`value_from_normalized_log`]: maps a value back to `t ∈ [0,1]`,
+/// used by the mesher to position the handle for the current value.
+pub(crate) fn normalized_log_from_value(value: f32, min: f32, max: f32) -> f32 {
+    if min >= 0.0 {
+        normalized_log_from_positive_value(value.max(min), min.max(f32::MIN_POSITIVE), max)
+    } else if max <= 0.0 {
+        1.0 - normalized_log_from_positive_value((-value).min(-min), (-max).max(f32::MIN_POSITIVE), -min)
+    } else {
+        normalized_log_from_symmetric_value(value, min, max)
+    }
+}
+
+fn positive_value_from_normalized_log(t: f32, min: f32, max: f32) -> f32 {
+    min * (max / min).powf(t)
+}
+
+fn normalized_log_from_positive_value(value: f32, min: f32, max: f32) -> f32 {
+    (value.max(min) / min).ln() / (max / min).ln()
+}
+
+/// Half-width, in value-space, of the region around zero that is mapped
+/// linearly rather than logarithmically.
+fn symmetric_log_linear_range(min: f32, max: f32) -> f32 {
+    (0.05 * min.abs().max(max.abs())).max(1e-4)
+}
+
+fn symmetric_value_from_normalized_log(t: f32, min: f32, max: f32) -> f32 {
+    let eps = symmetric_log_linear_range(min, max);
+    let neg_decades = ((-min) / eps).ln().max(0.0);
+    let pos_decades = (max / eps).ln().max(0.0);
+    let linear_span = 1.0;
+    let total = neg_decades + linear_span + pos_decades;
+    let t_neg_end = neg_decades / total;
+    let t_pos_start = (neg_decades + linear_span) / total;
+    // When one side's decades clamp to 0 (min or max sits inside `-eps..eps`)
+    // there's no log region on that side, so the linear region reaches all
+    // the way out to the real bound instead of to `eps`.
+    let linear_min = if neg_decades > 0.0 { -eps } else { min };
+    let linear_max = if pos_decades > 0.0 { eps } else { max };
+
+    if t <= t_neg_end && neg_decades > 0.0 {
+        let t2 = 1.0 - t / t_neg_end;
+        -eps * ((-min) / eps).powf(t2)
+    } else if t >= t_pos_start && pos_decades > 0.0 {
+        let t2 = (t - t_pos_start) / (1.0 - t_pos_start);
+        eps * (max / eps).powf(t2)
+    } else {
+        let t2 = remap_clamp(t, t_neg_end, t_pos_start, 0.0, 1.0);
+        lerp(linear_min, linear_max, t2)
+    }
+}
+
+fn normalized_log_from_symmetric_value(value: f32, min: f32, max: f32) -> f32 {
+    let eps = symmetric_log_linear_range(min, max);
+    let neg_decades = ((-min) / eps).ln().max(0.0);
+    let pos_decades = (max / eps).ln().max(0.0);
+    let linear_span = 1.0;
+    let total = neg_decades + linear_span + pos_decades;
+    let t_neg_end = neg_decades / total;
+    let t_pos_start = (neg_decades + linear_span) / total;
+    let linear_min = if neg_decades > 0.0 { -eps } else { min };
+    let linear_max = if pos_decades > 0.0 { eps } else { max };
+
+    if value <= -eps && neg_decades > 0.0 {
+        let frac = ((-value) / eps).ln() / neg_decades;
+        t_neg_end * (1.0 - frac)
+    } else if value >= eps && pos_decades > 0.0 {
+        let frac = (value / eps).ln() / pos_decades;
+        lerp(t_pos_start, 1.0, frac)
+    } else {
+        lerp(
+            t_neg_end,
+            t_pos_start,
+            remap_clamp(value, linear_min, linear_max, 0.0, 1.0),
+        )
+    }
+}
+
+fn lerp(min: f32, max: f32, t: f32) -> f32 {
+    min + (max - min) * t
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{value_from_normalized_log, normalized_log_from_value};
+
+    /// An asymmetric zero-crossing range (`max` close enough to zero that the
+    /// positive side has no log region) must stay within `[min, max]` and
+    /// never produce NaN, for every `t` including the endpoints.
+    #[test]
+    fn slider_logarithmic_asymmetric_zero_crossing_stays_in_range() {
+        let (min, max) = (-1000.0, 0.5);
+        let mut t = 0.0;
+        while t <= 1.0 {
+            let value = value_from_normalized_log(t, min, max);
+            assert!(!value.is_nan(), "t={} produced NaN", t);
+            assert!(
+                value >= min && value <= max,
+                "t={} produced {}, outside [{}, {}]",
+                t,
+                value,
+                min,
+                max
+            );
+            t += 0.01;
+        }
+        assert_eq!(value_from_normalized_log(0.0, min, max), min);
+        assert_eq!(value_from_normalized_log(1.0, min, max), max);
+
+        // And the endpoints should hold for the mirrored case, where it's the
+        // negative side that has no log region.
+        let (min, max) = (-0.5, 1000.0);
+        assert_eq!(value_from_normalized_log(0.0, min, max), min);
+        assert_eq!(value_from_normalized_log(1.0, min, max), max);
+
+        // The inverse map should round-trip the endpoints back to t=0/t=1.
+        assert_eq!(normalized_log_from_value(min, min, max), 0.0);
+        assert_eq!(normalized_log_from_value(max, min, max), 1.0);
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// A 2D analog of [`Slider`]: drag a handle inside a rectangle to edit two
+/// values at once, one per axis.
+#[derive(Debug)]
+pub struct XYPad<'a> {
+    value_x: &'a mut f32,
+    value_y: &'a mut f32,
+    x_range: (f32, f32),
+    y_range: (f32, f32),
+    id: Option<Id>,
+    size: Vec2,
+}
+
+impl<'a> XYPad<'a> {
+    pub fn new(
+        value_x: &'a mut f32,
+        value_y: &'a mut f32,
+        x_range: (f32, f32),
+        y_range: (f32, f32),
+    ) -> Self {
+        XYPad {
+            value_x,
+            value_y,
+            x_range,
+            y_range,
+            id: None,
+            size: vec2(128.0, 128.0),
+        }
+    }
+
+    pub fn id(mut self, id: Id) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    pub fn size(mut self, size: Vec2) -> Self {
+        self.size = size;
+        self
+    }
+}
+
+impl<'a> Widget for XYPad<'a> {
+    fn add_to(self, region: &mut Region) -> GuiResponse {
+        let XYPad {
+            value_x,
+            value_y,
+            x_range: (min_x, max_x),
+            y_range: (min_y, max_y),
+            id,
+            size,
+        } = self;
+        debug_assert!(min_x <= max_x);
+        debug_assert!(min_y <= max_y);
+        let id = region.combined_id(id);
+        let (rect, interact) = region.reserve_space(size, id);
+        let interact = region.resolve_interact(id, rect, interact);
+
+        if interact.active {
+            if let Some(mouse_pos) = region.input().mouse_pos {
+                *value_x = remap_clamp(mouse_pos.x, rect.min().x, rect.max().x, min_x, max_x);
+                *value_y = remap_clamp(mouse_pos.y, rect.min().y, rect.max().y, min_y, max_y);
+            }
+        }
+
+        region.add_graphic(GuiCmd::XYPad {
+            interact,
+            rect,
+            value_x: *value_x,
+            value_y: *value_y,
+            ranges: ((min_x, max_x), (min_y, max_y)),
+        });
+
+        region.response(interact)
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// A single-line text field that the user can click to focus and type into.
+#[derive(Debug)]
+pub struct TextEdit<'a> {
+    text: &'a mut String,
+    id: Option<Id>,
+    text_style: TextStyle,
+}
+
+impl<'a> TextEdit<'a> {
+    pub fn new(text: &'a mut String) -> Self {
+        TextEdit {
+            text,
+            id: None,
+            text_style: TextStyle::Body,
+        }
+    }
+
+    pub fn id(mut self, id: Id) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    pub fn text_style(mut self, text_style: TextStyle) -> Self {
+        self.text_style = text_style;
+        self
+    }
+}
+
+impl<'a> Widget for TextEdit<'a> {
+    fn add_to(self, region: &mut Region) -> GuiResponse {
+        let TextEdit {
+            text,
+            id,
+            text_style,
+        } = self;
+        let id = region
+            .combined_id(id)
+            .unwrap_or_else(|| make_id("text_edit"));
+        let font = &region.fonts()[text_style];
+        let (galley, text_size) = font.layout_multiline(text, region.width());
+        let line_height = font.line_spacing();
+        let text_cursor = region.cursor();
+        let (rect, interact) = region.reserve_space(text_size, Some(id));
+        let interact = region.resolve_interact(Some(id), rect, interact);
+
+        if interact.clicked {
+            region.memory().kb_focus_id = Some(id);
+        } else if region.input().mouse_clicked && !interact.hovered {
+            if region.memory().kb_focus_id == Some(id) {
+                region.memory().kb_focus_id = None;
+            }
+        }
+        let has_kb_focus = region.memory().kb_focus_id == Some(id);
+
+        let mut cursor = (*region
+            .memory()
+            .text_edit_cursor
+            .entry(id)
+            .or_insert_with(|| text.chars().count()))
+        .min(text.chars().count());
+
+        if has_kb_focus {
+            for event in region.input().events.clone() {
+                match event {
+                    Event::Text(text_to_insert) => {
+                        let byte_index = byte_index_of_char(text, cursor);
+                        text.insert_str(byte_index, &text_to_insert);
+                        cursor += text_to_insert.chars().count();
+                    }
+                    Event::KeyDown { key, .. } => match key {
+                        Key::Backspace if cursor > 0 => {
+                            let byte_index = byte_index_of_char(text, cursor - 1);
+                            text.remove(byte_index);
+                            cursor -= 1;
+                        }
+                        Key::Delete if cursor < text.chars().count() => {
+                            let byte_index = byte_index_of_char(text, cursor);
+                            text.remove(byte_index);
+                        }
+                        Key::Left => cursor = cursor.saturating_sub(1),
+                        Key::Right => cursor = (cursor + 1).min(text.chars().count()),
+                        Key::Home => cursor = 0,
+                        Key::End => cursor = text.chars().count(),
+                        _ => {}
+                    },
+                    Event::KeyUp { .. } => {}
+                }
+            }
+            region.memory().text_edit_cursor.insert(id, cursor);
+        }
+
+        if has_kb_focus {
+            let caret_x = x_offset_of_char(&galley, cursor);
+            let caret_top = text_cursor + vec2(caret_x, 0.0);
+            let caret_bottom = caret_top + vec2(0.0, line_height);
+            region.add_graphic(GuiCmd::PaintCommands(vec![PaintCmd::Line {
+                points: vec![caret_top, caret_bottom],
+                color: region.options().theme.text_color,
+                width: 1.0,
+            }]));
+        }
+
+        region.add_text_colored(text_cursor, text_style, galley, region.options().theme.text_color);
+        region.response(interact)
+    }
+}
+
+/// Byte index of the `char_index`'th character, for splitting/inserting/removing.
+fn byte_index_of_char(text: &str, char_index: usize) -> usize {
+    text.char_indices()
+        .nth(char_index)
+        .map(|(i, _)| i)
+        .unwrap_or_else(|| text.len())
+}
+
+/// The x position (relative to the start of the text) of the given character index,
+/// using the per-character offsets produced by `Font::layout_multiline`.
+fn x_offset_of_char(galley: &[(String, Vec<f32>)], char_index: usize) -> f32 {
+    let mut remaining = char_index;
+    for (line, x_offsets) in galley {
+        let len = line.chars().count();
+        if remaining <= len {
+            return x_offsets.get(remaining).copied().unwrap_or(0.0);
+        }
+        remaining -= len;
+    }
+    0.0
+}
+
+// ----------------------------------------------------------------------------
+// Drag and drop
+
+/// How far the mouse has to move past the press point before a
+/// `drag_source` turns into an actual drag, rather than a click.
+const DRAG_START_THRESHOLD: f32 = 4.0;
+
+impl Region {
+    /// Begin dragging `id` once the mouse has moved past a small threshold
+    /// while pressed on it, carrying `payload_fn()`'s result. While a drag
+    /// from `id` is in flight a ghost of it is painted at the mouse position;
+    /// pair with [`Region::drop_target`] on the receiving widget.
+    pub fn drag_source<T: Any>(&mut self, id: Id, payload_fn: impl FnOnce() -> T) {
+        let mouse_pos = self.input().mouse_pos;
+
+        if self.memory().dragged_id == Some(id) {
+            if !self.input().mouse_down && !self.input().mouse_released {
+                // The release happened on an earlier frame and nothing
+                // claimed the payload via `drop_target` in the meantime, so
+                // it's abandoned; sweep it up now. We don't clear on the
+                // release frame itself, since `drop_target` may not have run
+                // yet this frame (widget order shouldn't decide the drop).
+                self.memory().dragged_id = None;
+                self.memory().drag_payload = None;
+            } else if let Some(mouse_pos) = mouse_pos {
+                self.add_graphic(GuiCmd::PaintCommands(vec![PaintCmd::Rect {
+                    corner_radius: 0.0,
+                    fill_color: Some(self.options().theme.hovered_color),
+                    outline: None,
+                    rect: Rect::from_min_size(mouse_pos, vec2(64.0, 16.0)),
+                }]));
+            }
+            return;
+        }
+
+        if !self.is_active(id) {
+            self.memory().drag_start_pos.remove(&id);
+            return;
+        }
+
+        let start_pos = *self.memory().drag_start_pos.entry(id).or_insert_with(|| {
+            mouse_pos.unwrap_or_default()
+        });
+        let past_threshold = mouse_pos
+            .map(|pos| {
+                let dx = pos.x - start_pos.x;
+                let dy = pos.y - start_pos.y;
+                (dx * dx + dy * dy).sqrt() > DRAG_START_THRESHOLD
+            })
+            .unwrap_or(false);
+
+        if past_threshold {
+            self.memory().dragged_id = Some(id);
+            self.memory().drag_payload = Some(Box::new(payload_fn()));
+            self.memory().drag_start_pos.remove(&id);
+        }
+    }
+
+    /// If the mouse was just released over `id`'s rect while a payload of
+    /// type `T` is in flight, consume and return it; otherwise `None`.
+    pub fn drop_target<T: Any>(&mut self, id: Id) -> Option<T> {
+        if !self.input().mouse_released {
+            return None;
+        }
+        let mouse_pos = self.input().mouse_pos?;
+        let rect = self.hitbox_rect(id)?;
+        if !rect.contains(mouse_pos) {
+            return None;
+        }
+        let payload = self.memory().drag_payload.take()?;
+        match payload.downcast::<T>() {
+            Ok(payload) => {
+                self.memory().dragged_id = None;
+                Some(*payload)
+            }
+            Err(payload) => {
+                self.memory().drag_payload = Some(payload);
+                None
+            }
+        }
+    }
+}
+
 // ----------------------------------------------------------------------------
 
 pub struct Separator {
     line_width: f32,
     width: f32,
+    color: Option<Color>,
 }
 
 impl Separator {
@@ -275,6 +907,7 @@ impl Separator {
         Separator {
             line_width: 2.0,
             width: 6.0,
+            color: None,
         }
     }
 
@@ -289,9 +922,17 @@ impl Separator {
     }
 }
 
+impl Colorable for Separator {
+    fn color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+}
+
 impl Widget for Separator {
     fn add_to(self, region: &mut Region) -> GuiResponse {
         let available_space = region.available_space;
+        let color = self.color.unwrap_or(region.options().theme.separator_color);
         let (points, interact) = match region.direction() {
             Direction::Horizontal => {
                 let (rect, interact) =
@@ -318,7 +959,7 @@ impl Widget for Separator {
         };
         let paint_cmd = PaintCmd::Line {
             points,
-            color: Color::WHITE,
+            color,
             width: self.line_width,
         };
         region.add_graphic(GuiCmd::PaintCommands(vec![paint_cmd]));